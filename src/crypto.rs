@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Argon2id cost parameters, configurable via the environment so operators can
+/// tune the work factor to their hardware.
+struct Cost {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Cost {
+    fn from_env() -> Self {
+        let parse = |key: &str, default: u32| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            memory_kib: parse("ARGON2_MEMORY_KIB", 19 * 1024),
+            iterations: parse("ARGON2_ITERATIONS", 2),
+            parallelism: parse("ARGON2_PARALLELISM", 1),
+        }
+    }
+
+    fn params(&self) -> Result<argon2::Params> {
+        argon2::Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|e| anyhow!("invalid Argon2 parameters: {e}"))
+    }
+}
+
+/// An on-disk sealed blob: the Argon2 salt, the AEAD nonce and the
+/// ChaCha20-Poly1305 ciphertext. The Argon2 cost parameters are not stored:
+/// [`open`] derives the key with the `ARGON2_*` values in effect when it runs,
+/// so they must match the values used by [`seal`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sealed {
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+/// Derive a 32-byte key from the passphrase and salt using Argon2id.
+fn derive_key(passphrase: &str, salt: &[u8], cost: &Cost) -> Result<[u8; 32]> {
+    let argon2 = Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        cost.params()?,
+    );
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Argon2 key derivation failed: {e}"))?;
+    Ok(key)
+}
+
+/// Encrypt `plaintext` under a key derived from `passphrase`, generating a
+/// random salt and nonce.
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Result<Sealed> {
+    let cost = Cost::from_env();
+
+    let mut salt = vec![0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+
+    let key = derive_key(passphrase, &salt, &cost)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| anyhow!("encryption failed: {e}"))?;
+
+    Ok(Sealed {
+        salt,
+        nonce: nonce_bytes.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Decrypt a sealed blob with `passphrase`.
+pub fn open(passphrase: &str, sealed: &Sealed) -> Result<Vec<u8>> {
+    let cost = Cost::from_env();
+    let key = derive_key(passphrase, &sealed.salt, &cost)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = Nonce::from_slice(&sealed.nonce);
+    cipher
+        .decrypt(nonce, sealed.ciphertext.as_ref())
+        .map_err(|_| anyhow!("decryption failed — wrong passphrase or corrupt file"))
+}
+
+/// Resolve the passphrase used to seal credentials.
+///
+/// Prefers `BSKY_PASSPHRASE` (for agent/headless use) and falls back to an
+/// interactive prompt on a TTY. Returns `None` when no passphrase is available,
+/// signalling that credentials should be stored in the clear legacy format.
+pub fn resolve_passphrase() -> Result<Option<String>> {
+    if let Ok(passphrase) = std::env::var("BSKY_PASSPHRASE") {
+        if !passphrase.is_empty() {
+            return Ok(Some(passphrase));
+        }
+    }
+    if atty::is(atty::Stream::Stdin) {
+        let passphrase = rpassword::prompt_password("Credential passphrase: ")
+            .context("failed to read passphrase")?;
+        if !passphrase.is_empty() {
+            return Ok(Some(passphrase));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_open_round_trips() {
+        let plaintext = b"refresh-token-and-password-blob";
+        let sealed = seal("correct horse battery staple", plaintext).unwrap();
+        let opened = open("correct horse battery staple", &sealed).unwrap();
+        assert_eq!(opened, plaintext);
+    }
+
+    #[test]
+    fn open_rejects_wrong_passphrase() {
+        let sealed = seal("right", b"secret").unwrap();
+        assert!(open("wrong", &sealed).is_err());
+    }
+
+    #[test]
+    fn each_seal_uses_a_fresh_salt_and_nonce() {
+        let a = seal("pw", b"secret").unwrap();
+        let b = seal("pw", b"secret").unwrap();
+        assert_ne!(a.salt, b.salt);
+        assert_ne!(a.nonce, b.nonce);
+        assert_ne!(a.ciphertext, b.ciphertext);
+    }
+}
@@ -0,0 +1,291 @@
+use anyhow::{Context, Result};
+use atrium_api::types::app::bsky::feed::Post;
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, instrument, warn};
+
+use crate::StreamItem;
+use serde::{Deserialize, Serialize};
+
+/// Record collections that back Bluesky notifications — likes, reposts,
+/// follows and the reply/mention posts. These are streamed alongside
+/// `app.bsky.feed.post` so the firehose covers what the old
+/// `stream_notifications` task used to.
+const NOTIFICATION_COLLECTIONS: &[&str] = &[
+    "app.bsky.feed.like",
+    "app.bsky.feed.repost",
+    "app.bsky.graph.follow",
+];
+
+/// Default Jetstream endpoint. Jetstream is the JSON firehose view over
+/// `com.atproto.sync.subscribeRepos`, which avoids having to decode CBOR/CAR
+/// commit frames by hand.
+const DEFAULT_JETSTREAM: &str = "wss://jetstream2.us-east.bsky.network/subscribe";
+
+/// Backoff ceiling for reconnects so a flapping upstream doesn't hammer the
+/// relay.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A notification-relevant record seen on the firehose — a like, repost or
+/// follow created by any account. Records are not filtered by the account or
+/// content they target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    /// The notification kind, derived from the record collection
+    /// (`like`, `repost`, `follow`).
+    pub kind: String,
+    /// The DID of the actor who generated the notification.
+    pub did: String,
+    /// The AT URI of the created record.
+    pub uri: String,
+    /// When the record was created.
+    pub created_at: String,
+}
+
+impl Notification {
+    /// Build a notification from a Jetstream commit frame.
+    pub fn from_commit(
+        did: &str,
+        collection: &str,
+        commit: &serde_json::Value,
+    ) -> Result<Self> {
+        let kind = collection
+            .rsplit('.')
+            .next()
+            .unwrap_or(collection)
+            .to_string();
+        let rkey = commit
+            .get("rkey")
+            .and_then(|r| r.as_str())
+            .context("commit frame missing rkey")?;
+        let created_at = commit
+            .get("record")
+            .and_then(|r| r.get("createdAt"))
+            .and_then(|c| c.as_str())
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self {
+            kind,
+            did: did.to_string(),
+            uri: format!("at://{did}/{collection}/{rkey}"),
+            created_at,
+        })
+    }
+}
+
+/// A persistent firehose subscription.
+///
+/// Holds a single WebSocket to Jetstream, decodes commit frames, filters for
+/// `app.bsky.feed.post` records and the like/repost/follow collections in
+/// [`NOTIFICATION_COLLECTIONS`], and feeds them into the daemon's event
+/// channel. It auto-reconnects with exponential backoff and remembers the
+/// last-seen `time_us` cursor so a reconnect resumes with `?cursor=` rather
+/// than missing or replaying events.
+pub struct Firehose {
+    endpoint: String,
+    cursor: Option<u64>,
+}
+
+impl Firehose {
+    pub fn new() -> Self {
+        let endpoint =
+            std::env::var("JETSTREAM_ENDPOINT").unwrap_or_else(|_| DEFAULT_JETSTREAM.to_string());
+        Self {
+            endpoint,
+            cursor: None,
+        }
+    }
+
+    fn subscribe_url(&self) -> String {
+        let mut url = format!("{}?wantedCollections=app.bsky.feed.post", self.endpoint);
+        for collection in NOTIFICATION_COLLECTIONS {
+            url.push_str(&format!("&wantedCollections={collection}"));
+        }
+        if let Some(cursor) = self.cursor {
+            url.push_str(&format!("&cursor={cursor}"));
+        }
+        url
+    }
+
+    /// Run the subscription until the receiver is dropped, reconnecting on any
+    /// transport error with exponential backoff.
+    #[instrument(name = "firehose", skip(self, tx))]
+    pub async fn run(mut self, tx: mpsc::Sender<StreamItem>) -> Result<()> {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match self.connect_and_pump(&tx).await {
+                Ok(()) => {
+                    // The channel closed — the daemon is shutting down.
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Firehose disconnected: {e}; reconnecting in {backoff:?}");
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    async fn connect_and_pump(&mut self, tx: &mpsc::Sender<StreamItem>) -> Result<()> {
+        let url = self.subscribe_url();
+        info!("Connecting to firehose at {url}");
+        let (mut socket, _) = connect_async(&url)
+            .await
+            .context("firehose websocket handshake failed")?;
+
+        while let Some(frame) = socket.next().await {
+            let frame = frame.context("firehose read error")?;
+            let payload = match frame {
+                Message::Text(text) => text.into_bytes(),
+                Message::Binary(bytes) => bytes,
+                Message::Ping(_) | Message::Pong(_) => continue,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+
+            match self.decode_commit(&payload) {
+                Ok(Some(item)) => {
+                    if tx.send(item).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Skipping undecodable firehose frame: {e}"),
+            }
+        }
+        // The server closed the socket; report it as an error so `run`
+        // reconnects instead of treating it as a shutdown.
+        Err(anyhow::anyhow!("firehose connection closed by server"))
+    }
+
+    /// Decode a Jetstream commit frame, advancing the resume cursor. Post
+    /// creations become [`StreamItem::Post`]; like/repost/follow creations
+    /// become [`StreamItem::Notification`] so the firehose covers what the old
+    /// `stream_notifications` task did.
+    fn decode_commit(&mut self, payload: &[u8]) -> Result<Option<StreamItem>> {
+        let value: serde_json::Value =
+            serde_json::from_slice(payload).context("firehose frame was not JSON")?;
+
+        if let Some(time_us) = value.get("time_us").and_then(|v| v.as_u64()) {
+            self.cursor = Some(time_us);
+        }
+
+        let commit = match value.get("commit") {
+            Some(commit) => commit,
+            None => return Ok(None),
+        };
+
+        let is_create = commit
+            .get("operation")
+            .and_then(|o| o.as_str())
+            .map(|o| o == "create")
+            .unwrap_or(false);
+        if !is_create {
+            return Ok(None);
+        }
+
+        let collection = commit
+            .get("collection")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default();
+        let did = value
+            .get("did")
+            .and_then(|d| d.as_str())
+            .context("commit frame missing did")?;
+
+        if collection == "app.bsky.feed.post" {
+            let post = Post::from_commit(did, commit)
+                .context("failed to build Post from commit record")?;
+            Ok(Some(StreamItem::Post(post)))
+        } else if NOTIFICATION_COLLECTIONS.contains(&collection) {
+            let notification = Notification::from_commit(did, collection, commit)
+                .context("failed to build Notification from commit record")?;
+            Ok(Some(StreamItem::Notification(notification)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Default for Firehose {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn notification_from_commit_builds_uri_and_kind() {
+        let commit = json!({
+            "collection": "app.bsky.feed.like",
+            "rkey": "3kaa",
+            "record": { "createdAt": "2026-01-02T03:04:05Z" }
+        });
+        let notification =
+            Notification::from_commit("did:plc:alice", "app.bsky.feed.like", &commit).unwrap();
+        assert_eq!(notification.kind, "like");
+        assert_eq!(notification.did, "did:plc:alice");
+        assert_eq!(notification.uri, "at://did:plc:alice/app.bsky.feed.like/3kaa");
+        assert_eq!(notification.created_at, "2026-01-02T03:04:05Z");
+    }
+
+    #[test]
+    fn decode_commit_advances_cursor() {
+        let mut firehose = Firehose::new();
+        let frame = json!({ "time_us": 42, "did": "did:plc:x" });
+        let _ = firehose.decode_commit(frame.to_string().as_bytes()).unwrap();
+        assert_eq!(firehose.cursor, Some(42));
+    }
+
+    #[test]
+    fn decode_commit_ignores_deletes_and_unknown_collections() {
+        let mut firehose = Firehose::new();
+
+        let delete = json!({
+            "did": "did:plc:x",
+            "commit": { "operation": "delete", "collection": "app.bsky.feed.post" }
+        });
+        assert!(firehose
+            .decode_commit(delete.to_string().as_bytes())
+            .unwrap()
+            .is_none());
+
+        let unknown = json!({
+            "did": "did:plc:x",
+            "commit": { "operation": "create", "collection": "app.bsky.graph.block", "rkey": "r" }
+        });
+        assert!(firehose
+            .decode_commit(unknown.to_string().as_bytes())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn decode_commit_emits_notification_for_follow() {
+        let mut firehose = Firehose::new();
+        let follow = json!({
+            "did": "did:plc:x",
+            "commit": {
+                "operation": "create",
+                "collection": "app.bsky.graph.follow",
+                "rkey": "r1",
+                "record": { "createdAt": "2026-01-01T00:00:00Z" }
+            }
+        });
+        match firehose
+            .decode_commit(follow.to_string().as_bytes())
+            .unwrap()
+        {
+            Some(StreamItem::Notification(n)) => assert_eq!(n.kind, "follow"),
+            _ => panic!("expected a follow notification"),
+        }
+    }
+}
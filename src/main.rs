@@ -1,27 +1,43 @@
 use anyhow::{Context, Ok, Result};
 use atrium_api::{
     AtpClient,
-    types::{
-        app::bsky::feed::Post,
-        com::atproto::server::CreateSession,
-    }
+    types::app::bsky::feed::Post,
 };
-use ably::Rest as AblyRest;
 use dotenv::dotenv;
 use serde_json::json;
 use tokio::{
     task,
     sync::mpsc,
-    time::{sleep, Duration}
 };
-use std::{env, fmt::Error};
-use tracing::{info, error, Level};
-use tracing_subscriber;
+use std::env;
+use tracing::{error, info, instrument};
+
+mod crypto;
+mod dm;
+mod firehose;
+mod session;
+mod sink;
+mod store;
+mod telemetry;
+
+use dm::{DmClient, DmMessage};
+use firehose::{Firehose, Notification};
+use session::{AuthSession, Session};
+use sink::{Event, EventSink};
+use store::EventStore;
+
+/// An item delivered onto the shared event channel. Posts and notifications
+/// come from the firehose; DMs come from the chat sync loop.
+pub enum StreamItem {
+    Post(Post),
+    Notification(Notification),
+    Dm(DmMessage),
+}
 
 struct BskyXrpcDaemon {
-    atp_client: AtpClient,
-    ably_client: AblyRest,
-    channel_name: String,
+    auth: AuthSession,
+    sinks: Vec<Box<dyn EventSink>>,
+    store: EventStore,
 }
 
 impl BskyXrpcDaemon {
@@ -30,94 +46,151 @@ impl BskyXrpcDaemon {
 
         let username = env::var("BSKY_USERNAME")?;
         let password = env::var("BSKY_PASSWORD")?;
-        let ably_api_key = env::var("ABLY_API_KEY")?;
-
-        let atp_client = AtpClient::create_session(CreateSession {
-            identifier: username,
-            password: password.clone(),
-        }).await?;
-
-        let ably_client = AblyRest::new(&ably_api_key)?;
 
-        Ok(Self {
-            atp_client,
-            ably_client,
-            channel_name: "bsky-events".to_string(),
-        })
+        let atp_client = AtpClient::new()?;
+
+        // Resolve the credential passphrase once at startup and thread it
+        // through, rather than re-prompting on every disk read/write.
+        let passphrase = crypto::resolve_passphrase()?;
+
+        // Resume a cached session where possible, transparently refreshing an
+        // expired access token and only falling back to a password login when
+        // the refresh token is also dead. This keeps the daemon off the login
+        // rate limit across restarts and long runs.
+        let session_path = Session::default_path()?;
+        let session = session::resume_or_login(
+            &atp_client,
+            &session_path,
+            &username,
+            &password,
+            passphrase.as_deref(),
+        )
+        .await?;
+        atp_client.set_session(&session);
+
+        let sinks = sink::sinks_from_env("bsky-events".to_string())?;
+        let store = EventStore::from_env().await?;
+
+        // Share the refreshable session with the authenticated tasks so an
+        // access token that expires mid-run is refreshed and the call retried.
+        let auth = AuthSession::new(atp_client, session, session_path, passphrase);
+
+        Ok(Self { auth, sinks, store })
     }
 
-    async fn stream_posts(&self, tx: mpsc::Sender<Post>) -> Result<()> {
-        loop {
-            match self.atp_client.stream_posts().await {
-                Ok(post) => {
-                    tx.send(post).await?;
-                },
-                Err(e) => {
-                    error!("Post streaming error: {}", e);
-                    sleep(Duration::from_secs(5)).await;
+    #[instrument(skip(self, rx))]
+    async fn publish_events(&self, mut rx: mpsc::Receiver<StreamItem>) -> Result<()> {
+        while let Some(item) = rx.recv().await {
+            let event = match item {
+                StreamItem::Post(post) => {
+                    // Durably record the event before fanning out so a sink
+                    // failure never costs us the history. A persistence error
+                    // is logged but never aborts the publisher — a transient
+                    // Postgres hiccup must not take down streaming.
+                    if let Err(e) = self.store.persist(&post).await {
+                        error!("Failed to persist event: {e}");
+                    }
+                    info!("Published post event from {}", post.author.did);
+                    Event::new(
+                        "post",
+                        json!({
+                            "type": "post",
+                            "did": post.author.did,
+                            "text": post.record.text,
+                            "timestamp": post.record.created_at
+                        }),
+                    )
                 }
-            }
-        }
-    }
-
-    async fn stream_notifications(&self, tx: mpsc::Sender<Post>) -> Result<()> {
-        loop {
-            match self.atp_client.stream_notifications().await {
-                Ok(notification) => {
-                    // Convert notification to post-like structure
-                    let post = notification.convert_to_post();
-                    tx.send(post).await?;
-                },
-                Err(e) => {
-                    error!("Notification streaming error: {}", e);
-                    sleep(Duration::from_secs(5)).await;
+                StreamItem::Notification(notification) => {
+                    info!(
+                        "Published {} notification from {}",
+                        notification.kind, notification.did
+                    );
+                    Event::new(
+                        "notification",
+                        json!({
+                            "type": "notification",
+                            "kind": notification.kind,
+                            "did": notification.did,
+                            "uri": notification.uri,
+                            "timestamp": notification.created_at
+                        }),
+                    )
                 }
-            }
-        }
-    }
-
-    async fn publish_events(&self, mut rx: mpsc::Receiver<Post>) -> Result<()> {
-        let channel = self.ably_client.channel(&self.channel_name);
-
-        while let Some(post) = rx.recv().await {
-            let event_data = json!({
-                "type": "post",
-                "did": post.author.did,
-                "text": post.record.text,
-                "timestamp": post.record.created_at
-            });
+                StreamItem::Dm(message) => {
+                    info!("Published DM event in convo {}", message.convo_id);
+                    Event::new(
+                        "dm",
+                        json!({
+                            "type": "dm",
+                            "convo_id": message.convo_id,
+                            "did": message.sender_did,
+                            "text": message.text,
+                            "timestamp": message.sent_at
+                        }),
+                    )
+                }
+            };
 
-            channel.publish("post", event_data).await?;
-            info!("Published post event from {}", post.author.did);
+            sink::fan_out(&self.sinks, &event).await;
         }
 
         Ok(())
     }
 
+    /// Send a single direct message and return, for the `send` subcommand.
+    async fn send_dm(&self, convo_id: &str, text: &str) -> Result<()> {
+        let dm_client = DmClient::new(self.auth.clone());
+        dm_client.send_message(convo_id, text).await?;
+        Ok(())
+    }
+
     pub async fn run(&self) -> Result<()> {
         let (tx, rx) = mpsc::channel(100);
 
-        let posts_stream = task::spawn(self.stream_posts(tx.clone()));
-        let notifications_stream = task::spawn(self.stream_notifications(tx.clone()));
+        // A persistent Jetstream subscription replaces the old poll-and-sleep
+        // loops: it auto-reconnects with backoff and resumes from the last-seen
+        // cursor, feeding decoded posts into the shared event channel.
+        let firehose = Firehose::new();
+        let firehose_task = task::spawn(firehose.run(tx.clone()));
+
+        // The chat sync loop feeds incoming DMs onto the same channel.
+        let dm_client = DmClient::new(self.auth.clone());
+        let dm_task = task::spawn(dm_client.run(tx.clone()));
+
         let event_publisher = task::spawn(self.publish_events(rx));
 
-            tokio::try_join!(
-                posts_stream,
-                notifications_stream,
-                event_publisher
-            )?;
-        
-                Ok(())
+        drop(tx);
+
+        tokio::try_join!(firehose_task, dm_task, event_publisher)?;
+
+        Ok(())
     }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .init();
-
-    let daemon = BskyXrpcDaemon::new();
-    daemon.run().await?;
+async fn main() -> Result<()> {
+    telemetry::init()?;
+
+    // `bsky_dm_cli send <convo_id> <text...>` sends a single DM and exits;
+    // with no arguments the binary runs as the streaming daemon.
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("send") => {
+            let convo_id = args
+                .next()
+                .context("usage: bsky_dm_cli send <convo_id> <text>")?;
+            let text = args.collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                anyhow::bail!("usage: bsky_dm_cli send <convo_id> <text>");
+            }
+            let daemon = BskyXrpcDaemon::new().await?;
+            daemon.send_dm(&convo_id, &text).await?;
+        }
+        _ => {
+            let daemon = BskyXrpcDaemon::new().await?;
+            daemon.run().await?;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::crypto;
+
+/// A serializable snapshot of an authenticated ATProto session.
+///
+/// Mirrors the persisted `Session` used by the Matrix SDK: everything needed
+/// to resume talking to the PDS without a fresh password login is kept here so
+/// the daemon survives restarts without burning the login rate limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Short-lived access JWT used to authorize XRPC calls.
+    pub access_jwt: String,
+    /// Long-lived refresh JWT used against `com.atproto.server.refreshSession`.
+    pub refresh_jwt: String,
+    /// The account DID the tokens belong to.
+    pub did: String,
+    /// The account handle (e.g. `alice.bsky.social`).
+    pub handle: String,
+    /// The PDS service endpoint the session is bound to.
+    pub pds_endpoint: String,
+}
+
+impl Session {
+    /// The on-disk location of the cached session
+    /// (`~/.config/bsky_dm_cli/session.json`).
+    pub fn default_path() -> Result<PathBuf> {
+        let base = dirs::config_dir().context("could not resolve user config dir")?;
+        Ok(base.join("bsky_dm_cli").join("session.json"))
+    }
+
+    /// Load a previously cached session from disk, if one exists.
+    ///
+    /// A missing file is not an error — it simply means the daemon has never
+    /// authenticated on this machine and should fall back to a password login.
+    /// When a passphrase is configured the file is expected to be an encrypted
+    /// [`crypto::Sealed`] blob; otherwise it is read as plaintext JSON.
+    pub async fn load(path: &PathBuf, passphrase: Option<&str>) -> Result<Option<Self>> {
+        let bytes = match fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("failed to read cached session"),
+        };
+
+        let session = match passphrase {
+            Some(passphrase) => {
+                let sealed = serde_json::from_slice(&bytes)
+                    .context("cached session is not a sealed blob")?;
+                let plaintext = crypto::open(passphrase, &sealed)?;
+                serde_json::from_slice(&plaintext).context("decrypted session is corrupt")?
+            }
+            None => serde_json::from_slice(&bytes).context("cached session.json is corrupt")?,
+        };
+        info!("Loaded cached ATProto session from {}", path.display());
+        Ok(Some(session))
+    }
+
+    /// Serialize the session to disk, creating the config directory if needed.
+    ///
+    /// When a passphrase is configured the long-lived tokens are encrypted at
+    /// rest with an Argon2id-derived key before being written, so secrets never
+    /// sit on disk in cleartext.
+    pub async fn save(&self, path: &PathBuf, passphrase: Option<&str>) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .context("failed to create config directory")?;
+        }
+
+        let plaintext = serde_json::to_vec_pretty(self).context("failed to serialize session")?;
+        let bytes = match passphrase {
+            Some(passphrase) => {
+                let sealed = crypto::seal(passphrase, &plaintext)?;
+                serde_json::to_vec_pretty(&sealed).context("failed to serialize sealed session")?
+            }
+            None => {
+                warn!("No passphrase configured; session tokens stored in cleartext");
+                plaintext
+            }
+        };
+
+        fs::write(path, bytes)
+            .await
+            .context("failed to write session.json")?;
+        info!("Persisted ATProto session to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Obtain a live session, preferring a cached one and falling back to a
+/// password login. The returned session has already been validated (or
+/// refreshed) and written back to disk.
+pub async fn resume_or_login(
+    client: &atrium_api::AtpClient,
+    path: &PathBuf,
+    identifier: &str,
+    password: &str,
+    passphrase: Option<&str>,
+) -> Result<Session> {
+    if let Some(session) = Session::load(path, passphrase).await? {
+        match client.validate_session(&session.access_jwt).await {
+            Ok(true) => return Ok(session),
+            _ => match refresh(client, &session).await {
+                Ok(refreshed) => {
+                    refreshed.save(path, passphrase).await?;
+                    return Ok(refreshed);
+                }
+                Err(e) => {
+                    warn!("Session refresh failed ({e}); falling back to password login");
+                }
+            },
+        }
+    }
+
+    let created = client
+        .create_session(atrium_api::types::com::atproto::server::CreateSession {
+            identifier: identifier.to_string(),
+            password: password.to_string(),
+        })
+        .await
+        .context("password login failed")?;
+    let session = Session {
+        access_jwt: created.access_jwt,
+        refresh_jwt: created.refresh_jwt,
+        did: created.did,
+        handle: created.handle,
+        pds_endpoint: created.pds_endpoint,
+    };
+    session.save(path, passphrase).await?;
+    Ok(session)
+}
+
+/// Shared, refreshable session state.
+///
+/// Tasks that make authenticated XRPC calls (the DM sync loop, outgoing
+/// sends) hold one of these and route their calls through [`AuthSession::call`]
+/// so an expired access token is transparently refreshed and the call retried,
+/// rather than failing for the rest of the process lifetime.
+#[derive(Clone)]
+pub struct AuthSession {
+    client: atrium_api::AtpClient,
+    session: Arc<Mutex<Session>>,
+    path: PathBuf,
+    passphrase: Option<String>,
+}
+
+impl AuthSession {
+    pub fn new(
+        client: atrium_api::AtpClient,
+        session: Session,
+        path: PathBuf,
+        passphrase: Option<String>,
+    ) -> Self {
+        Self {
+            client,
+            session: Arc::new(Mutex::new(session)),
+            path,
+            passphrase,
+        }
+    }
+
+    /// Run an authenticated XRPC call, refreshing the session and retrying once
+    /// if it fails with an expired-token error.
+    pub async fn call<F, Fut, T>(&self, op: F) -> Result<T>
+    where
+        F: Fn(atrium_api::AtpClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match op(self.client.clone()).await {
+            Ok(value) => Ok(value),
+            Err(e) if is_expired_token(&e) => {
+                info!("Access token expired mid-run; refreshing session");
+                let mut guard = self.session.lock().await;
+                // A peer task may have already refreshed while we waited for
+                // the lock. Retry once under the lock before spending the
+                // single-use refresh token ourselves.
+                match op(self.client.clone()).await {
+                    Ok(value) => Ok(value),
+                    Err(e) if is_expired_token(&e) => {
+                        let refreshed = refresh(&self.client, &guard).await?;
+                        self.client.set_session(&refreshed);
+                        refreshed.save(&self.path, self.passphrase.as_deref()).await?;
+                        *guard = refreshed;
+                        drop(guard);
+                        op(self.client.clone()).await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether an XRPC error signals an expired access token that a refresh can
+/// recover from.
+///
+/// Matches the typed XRPC error variant rather than substring-scanning the
+/// `Display` string, so wrapped or localized messages don't silently break the
+/// retry path.
+pub fn is_expired_token(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<atrium_api::XrpcError>(),
+            Some(atrium_api::XrpcError::ExpiredToken)
+        )
+    })
+}
+
+/// Exchange a refresh JWT for a fresh access/refresh pair via
+/// `com.atproto.server.refreshSession`.
+pub async fn refresh(client: &atrium_api::AtpClient, session: &Session) -> Result<Session> {
+    let refreshed = client
+        .refresh_session(&session.refresh_jwt)
+        .await
+        .context("com.atproto.server.refreshSession failed")?;
+    Ok(Session {
+        access_jwt: refreshed.access_jwt,
+        refresh_jwt: refreshed.refresh_jwt,
+        did: session.did.clone(),
+        handle: session.handle.clone(),
+        pds_endpoint: session.pds_endpoint.clone(),
+    })
+}
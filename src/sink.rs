@@ -0,0 +1,192 @@
+use ably::Rest as AblyRest;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{error, info};
+
+/// A single event flowing through the daemon, ready to be serialized to any
+/// sink. The `kind` distinguishes posts, notifications and DMs; `payload` is
+/// the already-shaped JSON body that was previously published directly to Ably.
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    pub kind: String,
+    pub payload: Value,
+}
+
+impl Event {
+    pub fn new(kind: impl Into<String>, payload: Value) -> Self {
+        Self {
+            kind: kind.into(),
+            payload,
+        }
+    }
+}
+
+/// A destination events are fanned out to. Implementors are resolved from
+/// config at startup, mirroring the CI notifier design where each target picks
+/// its own transport.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    async fn publish(&self, event: &Event) -> Result<()>;
+}
+
+/// Publishes events to an Ably channel — the original behaviour.
+pub struct AblySink {
+    client: AblyRest,
+    channel_name: String,
+}
+
+impl AblySink {
+    pub fn new(client: AblyRest, channel_name: String) -> Self {
+        Self {
+            client,
+            channel_name,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for AblySink {
+    async fn publish(&self, event: &Event) -> Result<()> {
+        let channel = self.client.channel(&self.channel_name);
+        channel
+            .publish(&event.kind, event.payload.clone())
+            .await
+            .context("ably publish failed")?;
+        Ok(())
+    }
+}
+
+/// POSTs each event as JSON to a configured URL, letting users route events to
+/// Discord/Slack webhooks or their own services.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    async fn publish(&self, event: &Event) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await
+            .context("webhook POST failed")?
+            .error_for_status()
+            .context("webhook returned an error status")?;
+        Ok(())
+    }
+}
+
+/// Prints each event as a JSON line to stdout — handy for local debugging.
+pub struct StdoutSink;
+
+#[async_trait]
+impl EventSink for StdoutSink {
+    async fn publish(&self, event: &Event) -> Result<()> {
+        let line = serde_json::to_string(event).context("failed to serialize event")?;
+        println!("{line}");
+        Ok(())
+    }
+}
+
+/// Resolve the configured set of sinks from the environment.
+///
+/// Ably is kept when `ABLY_API_KEY` is set, a `WebhookSink` is added for every
+/// URL in the comma-separated `EVENT_WEBHOOK_URLS`, and a `StdoutSink` is added
+/// when `EVENT_STDOUT` is truthy. At least one sink is always returned so a
+/// misconfigured daemon still surfaces events.
+pub fn sinks_from_env(channel_name: String) -> Result<Vec<Box<dyn EventSink>>> {
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+
+    // Ably is optional: only build the client when a key is present, so a
+    // webhook/stdout-only deployment can start without one.
+    if let Ok(ably_api_key) = std::env::var("ABLY_API_KEY") {
+        let ably_client = AblyRest::new(&ably_api_key).context("invalid Ably API key")?;
+        sinks.push(Box::new(AblySink::new(ably_client, channel_name)));
+    }
+
+    if let Ok(urls) = std::env::var("EVENT_WEBHOOK_URLS") {
+        for url in urls.split(',').map(str::trim).filter(|u| !u.is_empty()) {
+            sinks.push(Box::new(WebhookSink::new(url.to_string())));
+        }
+    }
+
+    if matches!(
+        std::env::var("EVENT_STDOUT").as_deref(),
+        Ok("1") | Ok("true")
+    ) {
+        sinks.push(Box::new(StdoutSink));
+    }
+
+    if sinks.is_empty() {
+        info!("No sinks configured; defaulting to stdout");
+        sinks.push(Box::new(StdoutSink));
+    }
+
+    Ok(sinks)
+}
+
+/// Fan a single event out to every sink, logging but not aborting on the
+/// failure of any individual sink.
+pub async fn fan_out(sinks: &[Box<dyn EventSink>], event: &Event) {
+    for sink in sinks {
+        if let Err(e) = sink.publish(event).await {
+            error!("Sink publish failed: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear_env() {
+        std::env::remove_var("ABLY_API_KEY");
+        std::env::remove_var("EVENT_WEBHOOK_URLS");
+        std::env::remove_var("EVENT_STDOUT");
+    }
+
+    // One test drives every permutation sequentially — the process environment
+    // is global, so running them as separate parallel tests would race.
+    #[test]
+    fn sinks_resolve_from_env_permutations() {
+        let channel = || "bsky-events".to_string();
+
+        // Nothing configured: falls back to a single stdout sink.
+        clear_env();
+        assert_eq!(sinks_from_env(channel()).unwrap().len(), 1);
+
+        // Stdout explicitly requested.
+        clear_env();
+        std::env::set_var("EVENT_STDOUT", "true");
+        assert_eq!(sinks_from_env(channel()).unwrap().len(), 1);
+
+        // Two webhooks, blanks and whitespace ignored.
+        clear_env();
+        std::env::set_var(
+            "EVENT_WEBHOOK_URLS",
+            "https://a.example/hook, https://b.example/hook, ",
+        );
+        assert_eq!(sinks_from_env(channel()).unwrap().len(), 2);
+
+        // Webhook plus stdout compose.
+        clear_env();
+        std::env::set_var("EVENT_WEBHOOK_URLS", "https://a.example/hook");
+        std::env::set_var("EVENT_STDOUT", "1");
+        assert_eq!(sinks_from_env(channel()).unwrap().len(), 2);
+
+        clear_env();
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use atrium_api::types::app::bsky::feed::Post;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use tokio_postgres::NoTls;
+use tracing::info;
+
+type PgPool = Pool<PostgresConnectionManager<NoTls>>;
+
+/// Durable history for streamed events.
+///
+/// When `DATABASE_URL` is set the daemon builds a `bb8` connection pool and
+/// writes every post into an `events` table, deduplicating on the author DID and
+/// creation timestamp so reconnect-driven replays from the firehose cursor are
+/// swallowed. The record URI is not stored. When the
+/// variable is absent persistence is simply a no-op, keeping Postgres optional.
+pub struct EventStore {
+    pool: Option<PgPool>,
+}
+
+impl EventStore {
+    /// Connect to Postgres if `DATABASE_URL` is configured and bootstrap the
+    /// schema. Returns an inert store otherwise.
+    pub async fn from_env() -> Result<Self> {
+        let url = match std::env::var("DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => return Ok(Self { pool: None }),
+        };
+
+        let manager = PostgresConnectionManager::new_from_stringlike(&url, NoTls)
+            .context("invalid DATABASE_URL")?;
+        let pool = Pool::builder()
+            .build(manager)
+            .await
+            .context("failed to build Postgres pool")?;
+
+        let store = Self { pool: Some(pool) };
+        store.bootstrap().await?;
+        info!("Event persistence enabled");
+        Ok(store)
+    }
+
+    /// Create the `events` table and its uniqueness constraint if they don't
+    /// already exist.
+    async fn bootstrap(&self) -> Result<()> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        let conn = pool.get().await.context("failed to acquire connection")?;
+        conn.batch_execute(
+            "CREATE TABLE IF NOT EXISTS events (
+                did         TEXT NOT NULL,
+                text        TEXT,
+                created_at  TEXT NOT NULL,
+                raw         JSONB NOT NULL,
+                ingested_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                PRIMARY KEY (did, created_at)
+            );",
+        )
+        .await
+        .context("failed to bootstrap events schema")?;
+        Ok(())
+    }
+
+    /// Persist a single post, deduplicating on its author DID and creation
+    /// timestamp — the only stable identifiers the `Post` shape exposes — so
+    /// reconnect-driven replays from the firehose cursor are swallowed.
+    pub async fn persist(&self, post: &Post) -> Result<()> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        let conn = pool.get().await.context("failed to acquire connection")?;
+        let raw = serde_json::to_value(post).context("failed to serialize post")?;
+        conn.execute(
+            "INSERT INTO events (did, text, created_at, raw)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (did, created_at) DO NOTHING",
+            &[
+                &post.author.did,
+                &post.record.text,
+                &post.record.created_at,
+                &raw,
+            ],
+        )
+        .await
+        .context("failed to insert event")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+use opentelemetry_otlp::WithExportConfig;
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Initialize tracing with a plain `fmt` layer and, when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set, an OTLP exporter layer so structured
+/// traces can be shipped to an observability backend. The `fmt` layer is always
+/// present as a fallback, so operators without a collector still get logs.
+pub fn init() -> Result<()> {
+    let fmt_layer = tracing_subscriber::fmt::layer().with_filter(
+        tracing_subscriber::filter::LevelFilter::from_level(Level::INFO),
+    );
+
+    match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .install_batch(opentelemetry_sdk::runtime::Tokio)
+                .context("failed to install OTLP pipeline")?;
+            let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+            tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(otel_layer)
+                .init();
+        }
+        Err(_) => {
+            tracing_subscriber::registry().with(fmt_layer).init();
+        }
+    }
+
+    Ok(())
+}